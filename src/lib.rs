@@ -1,5 +1,6 @@
 use ansi_term::{Color, Style};
 use chrono::{DateTime, Local};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Mutex;
 use std::{fmt, io, io::Write as _};
 use tracing::{
@@ -8,49 +9,260 @@ use tracing::{
     Event, Level, Subscriber,
 };
 use tracing_subscriber::{
+    fmt::MakeWriter,
     layer::{Context, Layer},
     registry::LookupSpan,
 };
 
-#[derive(Debug)]
-pub struct HierarchicalLayer {
-    stdout: io::Stdout,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Tree,
+    Json,
+}
+
+pub struct HierarchicalLayer<W = fn() -> io::Stdout> {
+    make_writer: W,
     indent_amount: usize,
     ansi: bool,
     lck: Mutex<()>,
+    format: OutputFormat,
+    theme: Theme,
+    span_summary: bool,
+}
+
+impl<W> fmt::Debug for HierarchicalLayer<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HierarchicalLayer")
+            .field("indent_amount", &self.indent_amount)
+            .field("ansi", &self.ansi)
+            .field("format", &self.format)
+            .field("theme", &self.theme)
+            .field("span_summary", &self.span_summary)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(untagged)]
+pub enum ThemeColor {
+    Named(String),
+    Rgb(u8, u8, u8),
+}
+
+impl ThemeColor {
+    fn to_ansi_color(&self) -> Color {
+        match self {
+            ThemeColor::Rgb(r, g, b) => Color::RGB(*r, *g, *b),
+            ThemeColor::Named(name) => match name.to_ascii_lowercase().as_str() {
+                "black" => Color::Black,
+                "red" => Color::Red,
+                "green" => Color::Green,
+                "yellow" => Color::Yellow,
+                "blue" => Color::Blue,
+                "purple" => Color::Purple,
+                "cyan" => Color::Cyan,
+                "white" => Color::White,
+                _ => Color::White,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    pub trace_color: ThemeColor,
+    pub debug_color: ThemeColor,
+    pub info_color: ThemeColor,
+    pub warn_color: ThemeColor,
+    pub error_color: ThemeColor,
+    pub vertical_glyph: char,
+    pub branch_glyph: String,
+    pub fill_glyph: char,
+    pub wrap_width: usize,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            trace_color: ThemeColor::Named("purple".to_string()),
+            debug_color: ThemeColor::Named("blue".to_string()),
+            info_color: ThemeColor::Named("green".to_string()),
+            warn_color: ThemeColor::Rgb(252, 234, 160),
+            error_color: ThemeColor::Named("red".to_string()),
+            vertical_glyph: '┃',
+            branch_glyph: "┣━".to_string(),
+            fill_glyph: '━',
+            wrap_width: 200,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum FieldValue {
+    Debug(String),
+    Str(String),
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldValue::Debug(s) => write!(f, "{}", s),
+            FieldValue::Str(s) => write!(f, "{:?}", s),
+            FieldValue::Bool(b) => write!(f, "{}", b),
+            FieldValue::I64(v) => write!(f, "{}", v),
+            FieldValue::U64(v) => write!(f, "{}", v),
+            FieldValue::F64(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+impl FieldValue {
+    fn write_json(&self, buf: &mut impl fmt::Write) -> fmt::Result {
+        match self {
+            FieldValue::Debug(s) => write_json_string(buf, s),
+            FieldValue::Str(s) => write_json_string(buf, s),
+            FieldValue::Bool(b) => write!(buf, "{}", b),
+            FieldValue::I64(v) => write!(buf, "{}", v),
+            FieldValue::U64(v) => write!(buf, "{}", v),
+            FieldValue::F64(v) => {
+                if v.is_finite() {
+                    write!(buf, "{}", v)
+                } else {
+                    write!(buf, "null")
+                }
+            }
+        }
+    }
+}
+
+fn write_json_string(buf: &mut impl fmt::Write, s: &str) -> fmt::Result {
+    write!(buf, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(buf, "\\\"")?,
+            '\\' => write!(buf, "\\\\")?,
+            '\n' => write!(buf, "\\n")?,
+            '\r' => write!(buf, "\\r")?,
+            '\t' => write!(buf, "\\t")?,
+            c if (c as u32) < 0x20 => write!(buf, "\\u{:04x}", c as u32)?,
+            c => write!(buf, "{}", c)?,
+        }
+    }
+    write!(buf, "\"")
+}
+
+#[derive(Debug, Default)]
+struct LevelCounts {
+    trace: AtomicUsize,
+    debug: AtomicUsize,
+    info: AtomicUsize,
+    warn: AtomicUsize,
+    error: AtomicUsize,
+}
+
+impl LevelCounts {
+    fn record(&self, level: &Level) {
+        let counter = match *level {
+            Level::TRACE => &self.trace,
+            Level::DEBUG => &self.debug,
+            Level::INFO => &self.info,
+            Level::WARN => &self.warn,
+            Level::ERROR => &self.error,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn total(&self) -> usize {
+        self.trace.load(Ordering::Relaxed)
+            + self.debug.load(Ordering::Relaxed)
+            + self.info.load(Ordering::Relaxed)
+            + self.warn.load(Ordering::Relaxed)
+            + self.error.load(Ordering::Relaxed)
+    }
 }
 
 struct Data {
     start: DateTime<Local>,
-    kvs: Vec<(&'static str, String)>,
+    kvs: Vec<(&'static str, FieldValue)>,
+    event_counts: LevelCounts,
 }
 
-struct FmtEvent<'a> {
-    stdout: io::StdoutLock<'a>,
+struct FmtEvent<W> {
+    writer: W,
     comma: bool,
     buf: String,
+    vertical_glyph: char,
+    wrap_width: usize,
+}
+
+#[derive(Default)]
+struct JsonVisitor {
+    message: Option<String>,
+    fields: Vec<(&'static str, FieldValue)>,
 }
 
-impl<'a> FmtEvent<'a> {
+impl Visit for JsonVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = Some(format!("{:?}", value));
+        } else {
+            self.fields
+                .push((field.name(), FieldValue::Debug(format!("{:?}", value))));
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = Some(value.to_owned());
+        } else {
+            self.fields
+                .push((field.name(), FieldValue::Str(value.to_owned())));
+        }
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.fields.push((field.name(), FieldValue::Bool(value)));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.fields.push((field.name(), FieldValue::I64(value)));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.fields.push((field.name(), FieldValue::U64(value)));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.fields.push((field.name(), FieldValue::F64(value)));
+    }
+}
+
+impl<W: io::Write> FmtEvent<W> {
     fn print(&mut self, indent: usize, indent_amount: usize) {
         let mut idt = String::with_capacity(indent * indent_amount);
         let mut i = 0;
         while i < (indent * indent_amount) {
             if i % indent_amount == 0 {
-                idt.push('┃');
+                idt.push(self.vertical_glyph);
             } else {
                 idt.push(' ');
             }
             i += 1;
         }
-        let wrapper = textwrap::Wrapper::new(200 - idt.len())
+        let wrapper = textwrap::Wrapper::new(self.wrap_width.saturating_sub(idt.len()).max(1))
             .subsequent_indent(&idt)
             .break_words(true);
         let wrapped = wrapper.wrap(&self.buf);
         for w in &wrapped[0..wrapped.len() - 1] {
-            writeln!(self.stdout, "{}", w).unwrap();
+            writeln!(self.writer, "{}", w).unwrap();
         }
-        write!(self.stdout, "{}", wrapped[wrapped.len() - 1]).unwrap();
+        write!(self.writer, "{}", wrapped[wrapped.len() - 1]).unwrap();
     }
 }
 
@@ -59,6 +271,7 @@ impl Data {
         let mut span = Self {
             start: Local::now(),
             kvs: Vec::new(),
+            event_counts: LevelCounts::default(),
         };
         attrs.record(&mut span);
         span
@@ -67,11 +280,33 @@ impl Data {
 
 impl Visit for Data {
     fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
-        self.kvs.push((field.name(), format!("{:?}", value)))
+        self.kvs
+            .push((field.name(), FieldValue::Debug(format!("{:?}", value))))
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.kvs
+            .push((field.name(), FieldValue::Str(value.to_owned())))
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.kvs.push((field.name(), FieldValue::Bool(value)))
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.kvs.push((field.name(), FieldValue::I64(value)))
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.kvs.push((field.name(), FieldValue::U64(value)))
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.kvs.push((field.name(), FieldValue::F64(value)))
     }
 }
 
-impl<'a> Visit for FmtEvent<'a> {
+impl<W: io::Write> Visit for FmtEvent<W> {
     fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
         use fmt::Write;
         write!(
@@ -91,36 +326,73 @@ impl<'a> Visit for FmtEvent<'a> {
     }
 }
 
-struct ColorLevel<'a>(&'a Level);
+struct ColorLevel<'a> {
+    level: &'a Level,
+    theme: &'a Theme,
+}
 
 impl<'a> fmt::Display for ColorLevel<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match *self.0 {
-            Level::TRACE => Color::Purple.bold().paint("TRACE"),
-            Level::DEBUG => Color::Blue.bold().paint("DEBUG"),
-            Level::INFO => Color::Green.bold().paint(" INFO"),
-            Level::WARN => Color::RGB(252, 234, 160).bold().paint(" WARN"), // orange
-            Level::ERROR => Color::Red.bold().paint("ERROR"),
-        }
-        .fmt(f)
+        let (color, label) = match *self.level {
+            Level::TRACE => (self.theme.trace_color.to_ansi_color(), "TRACE"),
+            Level::DEBUG => (self.theme.debug_color.to_ansi_color(), "DEBUG"),
+            Level::INFO => (self.theme.info_color.to_ansi_color(), " INFO"),
+            Level::WARN => (self.theme.warn_color.to_ansi_color(), " WARN"),
+            Level::ERROR => (self.theme.error_color.to_ansi_color(), "ERROR"),
+        };
+        color.bold().paint(label).fmt(f)
     }
 }
 
-impl HierarchicalLayer {
+impl HierarchicalLayer<fn() -> io::Stdout> {
     pub fn new(indent_amount: usize) -> Self {
         let ansi = atty::is(atty::Stream::Stdout);
         Self {
             indent_amount,
-            stdout: io::stdout(),
+            make_writer: io::stdout,
             ansi,
             lck: Mutex::new(()),
+            format: OutputFormat::default(),
+            theme: Theme::default(),
+            span_summary: false,
         }
     }
+}
 
+impl<W> HierarchicalLayer<W> {
     pub fn with_ansi(self, ansi: bool) -> Self {
         Self { ansi, ..self }
     }
 
+    pub fn with_format(self, format: OutputFormat) -> Self {
+        Self { format, ..self }
+    }
+
+    pub fn with_theme(self, theme: Theme) -> Self {
+        Self { theme, ..self }
+    }
+
+    /// Replaces the writer output is written to (stdout by default), e.g. with one that writes
+    /// to a file or captures output into an in-memory buffer for tests.
+    pub fn with_writer<W2>(self, make_writer: W2) -> HierarchicalLayer<W2>
+    where
+        W2: MakeWriter,
+    {
+        HierarchicalLayer {
+            make_writer,
+            indent_amount: self.indent_amount,
+            ansi: self.ansi,
+            lck: self.lck,
+            format: self.format,
+            theme: self.theme,
+            span_summary: self.span_summary,
+        }
+    }
+
+    pub fn with_span_summary(self, span_summary: bool) -> Self {
+        Self { span_summary, ..self }
+    }
+
     fn styled(&self, style: Style, text: impl AsRef<str>) -> String {
         if self.ansi {
             style.paint(text.as_ref()).to_string()
@@ -175,13 +447,16 @@ impl HierarchicalLayer {
         let mut i = 0;
         while i < (indent * self.indent_amount) {
             if i % self.indent_amount == 0 {
-                idt.push('┃');
+                idt.push(self.theme.vertical_glyph);
             } else {
                 idt.push(' ');
             }
             i += 1;
         }
-        let wrapper = textwrap::Wrapper::new(200 + name_len - idt.len())
+        let width = (self.theme.wrap_width + name_len)
+            .saturating_sub(idt.len())
+            .max(1);
+        let wrapper = textwrap::Wrapper::new(width)
             .initial_indent(&idt)
             .subsequent_indent(&idt)
             .break_words(true);
@@ -192,27 +467,27 @@ impl HierarchicalLayer {
     }
 
     fn print_indent(&self, writer: &mut impl io::Write, indent: usize) -> io::Result<()> {
-        const LINE: &str = "┣━";
         let mut i = 0;
         while i < ((indent - 1) * self.indent_amount) {
             if i % self.indent_amount == 0 {
-                write!(writer, "┃")?;
+                write!(writer, "{}", self.theme.vertical_glyph)?;
             } else {
                 write!(writer, " ")?;
             }
             i += 1;
         }
-        write!(writer, "{}", LINE)?;
+        write!(writer, "{}", self.theme.branch_glyph)?;
         for _ in 0..self.indent_amount.saturating_sub(2) / 2 {
-            write!(writer, "━")?;
+            write!(writer, "{}", self.theme.fill_glyph)?;
         }
         Ok(())
     }
 }
 
-impl<S> Layer<S> for HierarchicalLayer
+impl<S, W> Layer<S> for HierarchicalLayer<W>
 where
     S: Subscriber + for<'span> LookupSpan<'span> + fmt::Debug,
+    W: MakeWriter + 'static,
 {
     fn new_span(&self, attrs: &Attributes, id: &Id, ctx: Context<S>) {
         let data = Data::new(attrs);
@@ -221,21 +496,43 @@ where
     }
 
     fn on_enter(&self, id: &tracing::Id, ctx: Context<S>) {
-        let mut stdout = self.stdout.lock();
         let span = ctx.span(&id).expect("in on_enter but span does not exist");
         let ext = span.extensions();
         let data = ext.get::<Data>().expect("span does not have data");
+        let name = span.metadata().name();
+
+        use fmt::Write;
+
+        let _guard = self.lck.lock().unwrap();
+        let mut writer = self.make_writer.make_writer();
+
+        if self.format == OutputFormat::Json {
+            let depth = ctx.scope().count();
+            let mut buf = String::new();
+            write!(&mut buf, "{{\"name\":").unwrap();
+            write_json_string(&mut buf, name).unwrap();
+            write!(&mut buf, ",\"kvs\":{{").unwrap();
+            for (i, (k, v)) in data.kvs.iter().enumerate() {
+                if i > 0 {
+                    write!(&mut buf, ",").unwrap();
+                }
+                write_json_string(&mut buf, k).unwrap();
+                write!(&mut buf, ":").unwrap();
+                v.write_json(&mut buf).unwrap();
+            }
+            write!(&mut buf, "}},\"start\":").unwrap();
+            write_json_string(&mut buf, &data.start.to_rfc3339()).unwrap();
+            write!(&mut buf, ",\"depth\":{}}}", depth).unwrap();
+            writeln!(writer, "{}", buf).unwrap();
+            return;
+        }
 
         let indent = ctx.scope().collect::<Vec<_>>().len() - 1;
-        // self.print_indent(&mut stdout, indent)
+        // self.print_indent(&mut writer, indent)
         //     .expect("Unable to write to stdout");
 
         let mut buf = String::new();
 
-        use fmt::Write;
-
-        let name = span.metadata().name();
-
         write!(
             &mut buf,
             "{name}",
@@ -256,17 +553,83 @@ where
             self.styled(Style::new().fg(Color::Green).bold(), "}") // Style::new().dimmed().paint("}")
         )
         .unwrap();
-        let _guard = self.lck.lock().unwrap();
-        self.print(&mut stdout, &buf, indent, name.len()).unwrap();
+        self.print(&mut writer, &buf, indent, name.len()).unwrap();
     }
 
     fn on_event(&self, event: &Event<'_>, ctx: Context<S>) {
-        let mut stdout = self.stdout.lock();
+        if self.span_summary {
+            let level = event.metadata().level();
+            for span in ctx.scope() {
+                let ext = span.extensions();
+                if let Some(data) = ext.get::<Data>() {
+                    data.event_counts.record(level);
+                }
+            }
+        }
+
+        if self.format == OutputFormat::Json {
+            // ctx.scope() yields root-to-leaf, so span_path reads like a
+            // breadcrumb.
+            let span_path: Vec<&str> = ctx
+                .scope()
+                .map(|span| span.metadata().name())
+                .collect();
+            let start = match ctx.current_span().id() {
+                Some(id) => ctx.span(id).map(|span| {
+                    let ext = span.extensions();
+                    ext.get::<Data>()
+                        .expect("Data cannot be found in extensions")
+                        .start
+                }),
+                None => None,
+            };
+            let elapsed_ms = start.map(|start| (Local::now() - start).num_milliseconds());
+
+            let mut visitor = JsonVisitor::default();
+            event.record(&mut visitor);
+
+            use fmt::Write;
+            let mut buf = String::new();
+            write!(&mut buf, "{{\"level\":").unwrap();
+            write_json_string(&mut buf, event.metadata().level().as_str()).unwrap();
+            write!(&mut buf, ",\"message\":").unwrap();
+            write_json_string(&mut buf, visitor.message.as_deref().unwrap_or("")).unwrap();
+            write!(&mut buf, ",\"fields\":{{").unwrap();
+            for (i, (k, v)) in visitor.fields.iter().enumerate() {
+                if i > 0 {
+                    write!(&mut buf, ",").unwrap();
+                }
+                write_json_string(&mut buf, k).unwrap();
+                write!(&mut buf, ":").unwrap();
+                v.write_json(&mut buf).unwrap();
+            }
+            write!(&mut buf, "}},\"elapsed_ms\":").unwrap();
+            match elapsed_ms {
+                Some(ms) => write!(&mut buf, "{}", ms).unwrap(),
+                None => write!(&mut buf, "null").unwrap(),
+            }
+            write!(&mut buf, ",\"span_path\":[").unwrap();
+            for (i, name) in span_path.iter().enumerate() {
+                if i > 0 {
+                    write!(&mut buf, ",").unwrap();
+                }
+                write_json_string(&mut buf, name).unwrap();
+            }
+            write!(&mut buf, "]}}").unwrap();
+
+            let _guard = self.lck.lock().unwrap();
+            let mut writer = self.make_writer.make_writer();
+            writeln!(writer, "{}", buf).unwrap();
+            return;
+        }
+
+        let _guard = self.lck.lock().unwrap();
+        let mut writer = self.make_writer.make_writer();
         // printing the indentation
         let indent = if let Some(_) = ctx.current_span().id() {
             // size hint isn't implemented on Scope.
             let indent = ctx.scope().collect::<Vec<_>>().len();
-            self.print_indent(&mut stdout, indent)
+            self.print_indent(&mut writer, indent)
                 .expect("Unable to write to stdout");
             indent
         } else {
@@ -294,12 +657,16 @@ where
             let elapsed = now - start;
             let level = event.metadata().level();
             let level = if self.ansi {
-                ColorLevel(level).to_string()
+                ColorLevel {
+                    level,
+                    theme: &self.theme,
+                }
+                .to_string()
             } else {
                 level.to_string()
             };
             write!(
-                &mut stdout,
+                &mut writer,
                 "{timestamp}{unit} {level}",
                 timestamp = self.styled(
                     Style::new().dimmed(),
@@ -311,15 +678,161 @@ where
             .expect("Unable to write to stdout");
         }
         let mut visitor = FmtEvent {
-            stdout,
+            writer,
             comma: false,
             buf: String::new(),
+            vertical_glyph: self.theme.vertical_glyph,
+            wrap_width: self.theme.wrap_width,
         };
         event.record(&mut visitor);
-        let _guard = self.lck.lock();
         visitor.print(indent, self.indent_amount);
-        writeln!(&mut visitor.stdout).unwrap();
+        writeln!(&mut visitor.writer).unwrap();
     }
 
-    fn on_close(&self, _: Id, _: Context<S>) {}
+    fn on_close(&self, id: Id, ctx: Context<S>) {
+        let span = ctx.span(&id).expect("in on_close but span does not exist");
+        let ext = span.extensions();
+        let data = ext.get::<Data>().expect("span does not have data");
+        let name = span.metadata().name();
+        let duration_ms = (Local::now() - data.start).num_milliseconds();
+        let depth = span.scope().count();
+        let indent = depth.saturating_sub(1);
+
+        use fmt::Write;
+
+        let _guard = self.lck.lock().unwrap();
+        let mut writer = self.make_writer.make_writer();
+
+        if self.format == OutputFormat::Json {
+            let mut buf = String::new();
+            write!(&mut buf, "{{\"name\":").unwrap();
+            write_json_string(&mut buf, name).unwrap();
+            write!(&mut buf, ",\"duration_ms\":{}", duration_ms).unwrap();
+            write!(&mut buf, ",\"depth\":{}", depth).unwrap();
+            if self.span_summary {
+                let counts = &data.event_counts;
+                write!(
+                    &mut buf,
+                    ",\"event_counts\":{{\"trace\":{},\"debug\":{},\"info\":{},\"warn\":{},\"error\":{}}}",
+                    counts.trace.load(Ordering::Relaxed),
+                    counts.debug.load(Ordering::Relaxed),
+                    counts.info.load(Ordering::Relaxed),
+                    counts.warn.load(Ordering::Relaxed),
+                    counts.error.load(Ordering::Relaxed),
+                )
+                .unwrap();
+            } else {
+                write!(&mut buf, ",\"event_counts\":null").unwrap();
+            }
+            write!(&mut buf, "}}").unwrap();
+            writeln!(writer, "{}", buf).unwrap();
+            return;
+        }
+
+        let mut buf = String::new();
+        write!(
+            &mut buf,
+            "{name} closed after {duration_ms}ms",
+            name = self.styled(Style::new().fg(Color::Green).bold(), name),
+            duration_ms = duration_ms,
+        )
+        .unwrap();
+        if self.span_summary {
+            let counts = &data.event_counts;
+            write!(
+                &mut buf,
+                ", {total} event(s): TRACE {trace}, DEBUG {debug}, INFO {info}, WARN {warn}, ERROR {error}",
+                total = counts.total(),
+                trace = counts.trace.load(Ordering::Relaxed),
+                debug = counts.debug.load(Ordering::Relaxed),
+                info = counts.info.load(Ordering::Relaxed),
+                warn = counts.warn.load(Ordering::Relaxed),
+                error = counts.error.load(Ordering::Relaxed),
+            )
+            .unwrap();
+        }
+        self.print(&mut writer, &buf, indent, name.len()).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn json_output_goes_to_the_configured_writer() {
+        let buf = SharedBuf::default();
+        let layer = HierarchicalLayer::new(2)
+            .with_writer({
+                let buf = buf.clone();
+                move || buf.clone()
+            })
+            .with_format(OutputFormat::Json);
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("my_span");
+            let _guard = span.enter();
+            tracing::info!(answer = 42, "hello");
+        });
+
+        let output = String::from_utf8(buf.0.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("\"message\":\"hello\""));
+        assert!(output.contains("\"answer\":42"));
+        assert!(output.contains("\"span_path\":[\"my_span\"]"));
+    }
+
+    #[test]
+    fn theme_deserializes_named_and_rgb_colors_and_fills_in_defaults() {
+        let theme: Theme = serde_json::from_str(r#"{"warn_color": "cyan"}"#).unwrap();
+        assert!(matches!(theme.warn_color, ThemeColor::Named(ref s) if s == "cyan"));
+        assert_eq!(theme.wrap_width, Theme::default().wrap_width);
+        assert_eq!(theme.vertical_glyph, Theme::default().vertical_glyph);
+
+        let theme: Theme = serde_json::from_str(r#"{"error_color": [1, 2, 3]}"#).unwrap();
+        assert!(matches!(theme.error_color, ThemeColor::Rgb(1, 2, 3)));
+    }
+
+    #[test]
+    fn on_close_reports_duration_and_per_level_event_counts() {
+        let buf = SharedBuf::default();
+        let layer = HierarchicalLayer::new(2)
+            .with_writer({
+                let buf = buf.clone();
+                move || buf.clone()
+            })
+            .with_format(OutputFormat::Json)
+            .with_span_summary(true);
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("my_span");
+            let _guard = span.enter();
+            tracing::info!("one");
+            tracing::warn!("two");
+        });
+
+        let output = String::from_utf8(buf.0.lock().unwrap().clone()).unwrap();
+        let close_line = output.lines().last().unwrap();
+        assert!(close_line.contains("\"name\":\"my_span\""));
+        assert!(close_line.contains("\"duration_ms\":"));
+        assert!(close_line.contains(
+            "\"event_counts\":{\"trace\":0,\"debug\":0,\"info\":1,\"warn\":1,\"error\":0}"
+        ));
+    }
 }